@@ -0,0 +1,343 @@
+use std::collections::HashMap;
+
+use crate::{Operators, Vm, TRAP};
+
+const MEM_MAX:usize = 1 << 16;
+
+enum StmtKind {
+    Instruction { mnemonic:String, operands:Vec<String> },
+    Fill(String),
+    Blkw(u16),
+    Stringz(String),
+}
+
+struct Stmt {
+    address:u16,
+    kind:StmtKind,
+}
+
+type SymbolTable = HashMap<String,u16>;
+type PassOneResult = Result<(u16,Vec<Stmt>,SymbolTable),String>;
+
+/// Assembles LC-3 assembly source into a big-endian `.obj` byte stream in
+/// the same format `Vm::read_image` consumes: the origin word followed by
+/// the swapped instruction words. Uses the standard two-pass approach:
+/// pass one builds a symbol table while tracking a location counter seeded
+/// from `.orig`, pass two encodes each instruction/directive, resolving
+/// labels and checking that offsets fit their field width.
+pub fn assemble(source:&str) -> Result<Vec<u8>,String> {
+    let lines = strip_comments(source);
+    let (origin,stmts,symbols) = pass_one(&lines)?;
+    let words = pass_two(origin,&stmts,&symbols)?;
+    Ok(encode(origin,&words))
+}
+
+fn strip_comments(source:&str) -> Vec<String> {
+    source
+        .lines()
+        .map(|line| match line.find(';') {
+            Some(i) => line[..i].trim().to_string(),
+            None => line.trim().to_string(),
+        })
+        .filter(|line| !line.is_empty())
+        .collect()
+}
+
+fn tokenize(line:&str) -> Vec<String> {
+    line.replace(',', " ")
+        .split_whitespace()
+        .map(|s| s.to_string())
+        .collect()
+}
+
+fn is_directive(tok:&str) -> bool {
+    matches!(tok.to_uppercase().as_str(), ".ORIG" | ".FILL" | ".BLKW" | ".STRINGZ" | ".END")
+}
+
+fn is_mnemonic(tok:&str) -> bool {
+    let upper = tok.to_uppercase();
+    if let Some(flags) = upper.strip_prefix("BR") {
+        if flags.chars().all(|c| matches!(c, 'N' | 'Z' | 'P')) {
+            return true;
+        }
+    }
+    matches!(upper.as_str(),
+        "ADD" | "LD" | "ST" | "JSR" | "JSRR" | "AND" | "LDR" | "STR" | "RTI" | "NOT" |
+        "LDI" | "STI" | "JMP" | "RET" | "RES" | "LEA" | "TRAP" |
+        "GETC" | "OUT" | "PUTS" | "IN" | "PUTSP" | "HALT")
+}
+
+fn pass_one(lines:&[String]) -> PassOneResult {
+    let mut lines = lines.iter();
+    let first = lines.next().ok_or("empty source")?;
+    let first_tokens = tokenize(first);
+    if first_tokens.first().map(|t| t.to_uppercase()) != Some(".ORIG".to_string()) {
+        return Err("program must start with .ORIG".to_string());
+    }
+    let origin = parse_imm(first_tokens.get(1).ok_or(".ORIG needs an address")?)? as u16;
+
+    let mut symbols = HashMap::new();
+    let mut stmts = Vec::new();
+    let mut loc:usize = origin as usize;
+
+    for line in lines {
+        let mut tokens = tokenize(line);
+        if tokens.is_empty() {
+            continue;
+        }
+        if tokens[0].to_uppercase() == ".END" {
+            break;
+        }
+        if loc >= MEM_MAX {
+            return Err("program does not fit in memory".to_string());
+        }
+        if !is_directive(&tokens[0]) && !is_mnemonic(&tokens[0]) {
+            symbols.insert(tokens.remove(0), loc as u16);
+            if tokens.is_empty() {
+                continue;
+            }
+        }
+
+        let address = loc as u16;
+        let directive = tokens[0].to_uppercase();
+        let kind = match directive.as_str() {
+            ".FILL" => {
+                loc = checked_advance(loc,1)?;
+                StmtKind::Fill(tokens.get(1).ok_or(".FILL needs a value")?.clone())
+            }
+            ".BLKW" => {
+                let n = parse_imm(tokens.get(1).ok_or(".BLKW needs a count")?)? as u16;
+                loc = checked_advance(loc,n as usize)?;
+                StmtKind::Blkw(n)
+            }
+            ".STRINGZ" => {
+                let text = parse_string(line)?;
+                loc = checked_advance(loc,text.chars().count() + 1)?;
+                StmtKind::Stringz(text)
+            }
+            _ => {
+                loc = checked_advance(loc,1)?;
+                StmtKind::Instruction { mnemonic:tokens.remove(0), operands:tokens }
+            }
+        };
+        stmts.push(Stmt { address, kind });
+    }
+
+    Ok((origin,stmts,symbols))
+}
+
+fn pass_two(origin:u16, stmts:&[Stmt], symbols:&SymbolTable) -> Result<Vec<u16>,String> {
+    let mut words = Vec::new();
+    for stmt in stmts {
+        match &stmt.kind {
+            StmtKind::Fill(value) => words.push(resolve_value(value,symbols)?),
+            StmtKind::Blkw(n) => words.extend(vec![0u16; *n as usize]),
+            StmtKind::Stringz(text) => {
+                words.extend(text.chars().map(|c| c as u16));
+                words.push(0);
+            }
+            StmtKind::Instruction { mnemonic, operands } => {
+                words.push(encode_instruction(stmt.address,mnemonic,operands,symbols)?);
+            }
+        }
+    }
+    if origin as usize + words.len() > MEM_MAX {
+        return Err("program does not fit in memory".to_string());
+    }
+    Ok(words)
+}
+
+fn encode(origin:u16, words:&[u16]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity((words.len()+1)*2);
+    push_word(&mut bytes,origin);
+    for &word in words {
+        push_word(&mut bytes,word);
+    }
+    bytes
+}
+
+fn push_word(bytes:&mut Vec<u8>, word:u16) {
+    let swapped = Vm::swap16(word);
+    bytes.push((swapped & 0xFF) as u8);
+    bytes.push((swapped >> 8) as u8);
+}
+
+fn encode_instruction(address:u16, mnemonic:&str, operands:&[String], symbols:&SymbolTable) -> Result<u16,String> {
+    let upper = mnemonic.to_uppercase();
+    if let Some(flags) = upper.strip_prefix("BR") {
+        let (n,z,p) = if flags.is_empty() {
+            (1,1,1)
+        } else {
+            (flags.contains('N') as u16, flags.contains('Z') as u16, flags.contains('P') as u16)
+        };
+        let cond = (n << 2) | (z << 1) | p;
+        let offset = encode_pc_offset(address,operand(operands,0,"BR")?,symbols,9)?;
+        return Ok((Operators::BR as u16) << 12 | cond << 9 | offset);
+    }
+
+    match upper.as_str() {
+        "ADD" | "AND" => {
+            let opcode = if upper == "ADD" { Operators::ADD } else { Operators::AND };
+            let dr = parse_reg(operand(operands,0,&upper)?)?;
+            let sr1 = parse_reg(operand(operands,1,&upper)?)?;
+            let third = operand(operands,2,&upper)?;
+            let word = match parse_reg(third) {
+                Ok(sr2) => (opcode as u16) << 12 | dr << 9 | sr1 << 6 | sr2,
+                Err(_) => {
+                    let imm = parse_imm(third)?;
+                    if !fits_signed(imm,5) {
+                        return Err(format!("immediate {} does not fit in 5 bits",imm));
+                    }
+                    (opcode as u16) << 12 | dr << 9 | sr1 << 6 | 1 << 5 | (imm as u16 & 0x1F)
+                }
+            };
+            Ok(word)
+        }
+        "NOT" => {
+            let dr = parse_reg(operand(operands,0,"NOT")?)?;
+            let sr = parse_reg(operand(operands,1,"NOT")?)?;
+            Ok((Operators::NOT as u16) << 12 | dr << 9 | sr << 6 | 0x3F)
+        }
+        "LD" | "ST" | "LDI" | "STI" | "LEA" => {
+            let opcode = match upper.as_str() {
+                "LD" => Operators::LD,
+                "ST" => Operators::ST,
+                "LDI" => Operators::LDI,
+                "STI" => Operators::STI,
+                _ => Operators::LEA,
+            };
+            let r0 = parse_reg(operand(operands,0,&upper)?)?;
+            let offset = encode_pc_offset(address,operand(operands,1,&upper)?,symbols,9)?;
+            Ok((opcode as u16) << 12 | r0 << 9 | offset)
+        }
+        "LDR" | "STR" => {
+            let opcode = if upper == "LDR" { Operators::LDR } else { Operators::STR };
+            let r0 = parse_reg(operand(operands,0,&upper)?)?;
+            let r1 = parse_reg(operand(operands,1,&upper)?)?;
+            let offset = parse_imm(operand(operands,2,&upper)?)?;
+            if !fits_signed(offset,6) {
+                return Err(format!("offset {} does not fit in 6 bits",offset));
+            }
+            Ok((opcode as u16) << 12 | r0 << 9 | r1 << 6 | (offset as u16 & 0x3F))
+        }
+        "JMP" => {
+            let r1 = parse_reg(operand(operands,0,"JMP")?)?;
+            Ok((Operators::JMP as u16) << 12 | r1 << 6)
+        }
+        "RET" => Ok((Operators::JMP as u16) << 12 | 7 << 6),
+        "JSRR" => {
+            let r1 = parse_reg(operand(operands,0,"JSRR")?)?;
+            Ok((Operators::JSR as u16) << 12 | r1 << 6)
+        }
+        "JSR" => {
+            let offset = encode_pc_offset(address,operand(operands,0,"JSR")?,symbols,11)?;
+            Ok((Operators::JSR as u16) << 12 | 1 << 11 | offset)
+        }
+        "RTI" => Ok((Operators::RTI as u16) << 12),
+        "RES" => Ok((Operators::RES as u16) << 12),
+        "TRAP" => {
+            let vect = parse_imm(operand(operands,0,"TRAP")?)? as u16;
+            Ok((Operators::TRAP as u16) << 12 | (vect & 0xFF))
+        }
+        "GETC" => Ok((Operators::TRAP as u16) << 12 | TRAP::GETC as u16),
+        "OUT" => Ok((Operators::TRAP as u16) << 12 | TRAP::OUT as u16),
+        "PUTS" => Ok((Operators::TRAP as u16) << 12 | TRAP::PUTS as u16),
+        "IN" => Ok((Operators::TRAP as u16) << 12 | TRAP::IN as u16),
+        "PUTSP" => Ok((Operators::TRAP as u16) << 12 | TRAP::PUTSP as u16),
+        "HALT" => Ok((Operators::TRAP as u16) << 12 | TRAP::HALT as u16),
+        other => Err(format!("unknown mnemonic: {}",other)),
+    }
+}
+
+fn operand<'a>(operands:&'a [String], index:usize, mnemonic:&str) -> Result<&'a String,String> {
+    operands.get(index).ok_or_else(|| format!("{} is missing an operand",mnemonic))
+}
+
+fn encode_pc_offset(address:u16, operand:&str, symbols:&SymbolTable, bits:u32) -> Result<u16,String> {
+    let target = resolve_value(operand,symbols)?;
+    let offset = target as i32 - (address as i32 + 1);
+    if !fits_signed(offset,bits) {
+        return Err(format!("offset {} to '{}' does not fit in {} bits",offset,operand,bits));
+    }
+    Ok((offset as u16) & mask(bits))
+}
+
+fn resolve_value(operand:&str, symbols:&SymbolTable) -> Result<u16,String> {
+    if looks_like_immediate(operand) {
+        Ok(parse_imm(operand)? as u16)
+    } else {
+        symbols.get(operand).copied().ok_or_else(|| format!("undefined label: {}",operand))
+    }
+}
+
+fn looks_like_immediate(tok:&str) -> bool {
+    let tok = tok.strip_prefix('-').unwrap_or(tok);
+    tok.starts_with('#') || tok.starts_with('x') || tok.starts_with('X') ||
+        tok.chars().next().is_some_and(|c| c.is_ascii_digit())
+}
+
+fn parse_imm(tok:&str) -> Result<i32,String> {
+    let (neg,tok) = match tok.strip_prefix('-') {
+        Some(rest) => (true,rest),
+        None => (false,tok),
+    };
+    let tok = tok.strip_prefix('#').unwrap_or(tok);
+    let value = match tok.strip_prefix('x').or_else(|| tok.strip_prefix('X')) {
+        Some(hex) => i32::from_str_radix(hex,16).map_err(|_| format!("invalid hex literal: {}",tok))?,
+        None => tok.parse::<i32>().map_err(|_| format!("invalid number: {}",tok))?,
+    };
+    Ok(if neg { -value } else { value })
+}
+
+fn parse_reg(tok:&str) -> Result<u16,String> {
+    if tok.len() == 2 {
+        let mut chars = tok.chars();
+        if matches!(chars.next(), Some('R') | Some('r')) {
+            if let Some(n) = chars.next().and_then(|c| c.to_digit(10)) {
+                if n <= 7 {
+                    return Ok(n as u16);
+                }
+            }
+        }
+    }
+    Err(format!("invalid register: {}",tok))
+}
+
+fn parse_string(line:&str) -> Result<String,String> {
+    let start = line.find('"').ok_or(".STRINGZ needs a quoted string")?;
+    let end = line.rfind('"').filter(|&e| e > start).ok_or(".STRINGZ needs a closing quote")?;
+    let mut result = String::new();
+    let mut chars = line[start+1..end].chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('n') => result.push('\n'),
+                Some('t') => result.push('\t'),
+                Some(other) => result.push(other),
+                None => {}
+            }
+        } else {
+            result.push(c);
+        }
+    }
+    Ok(result)
+}
+
+fn checked_advance(loc:usize, n:usize) -> Result<usize,String> {
+    let next = loc + n;
+    if next > MEM_MAX {
+        Err("program does not fit in memory".to_string())
+    } else {
+        Ok(next)
+    }
+}
+
+fn fits_signed(value:i32, bits:u32) -> bool {
+    let min = -(1i32 << (bits - 1));
+    let max = (1i32 << (bits - 1)) - 1;
+    value >= min && value <= max
+}
+
+fn mask(bits:u32) -> u16 {
+    ((1u32 << bits) - 1) as u16
+}
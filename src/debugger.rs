@@ -0,0 +1,222 @@
+use std::collections::HashSet;
+use std::io::{self, Write};
+
+use crate::disassembler::disasm;
+use crate::{Registers, StepOutcome, Vm};
+
+/// Interactive single-step debugger that drives a `Vm`'s fetch-decode loop
+/// instead of letting it run to completion unattended.
+pub struct Debugger {
+    breakpoints: HashSet<u16>,
+    last_command: Option<String>,
+    repeat: u32,
+    trace_only: bool,
+}
+
+impl Debugger {
+    pub fn new() -> Debugger {
+        Debugger {
+            breakpoints: HashSet::new(),
+            last_command: None,
+            repeat: 0,
+            trace_only: false,
+        }
+    }
+
+    /// Drives `vm` from a REPL prompt until the user quits or the program halts.
+    pub fn run(&mut self, vm: &mut Vm) {
+        println!("Entering debugger. Type 'help' for a list of commands.");
+        loop {
+            print!("(lc3db) ");
+            io::stdout().flush().expect("failed to flush");
+
+            let mut line = String::new();
+            if io::stdin().read_line(&mut line).unwrap_or(0) == 0 {
+                break;
+            }
+            let args:Vec<&str> = line.split_whitespace().collect();
+            match self.run_debugger_command(vm, &args) {
+                Ok(true) => {}
+                Ok(false) => break,
+                Err(e) => println!("error: {}", e),
+            }
+        }
+    }
+
+    /// Parses and executes a single REPL command. Returns `Ok(false)` when the
+    /// debugger should exit, `Err` on a malformed command.
+    pub fn run_debugger_command(&mut self, vm: &mut Vm, args: &[&str]) -> Result<bool, String> {
+        let args = if args.is_empty() {
+            match self.last_command.clone() {
+                Some(cmd) => return self.repeat_last(vm, &cmd),
+                None => return Ok(true),
+            }
+        } else {
+            args
+        };
+
+        match args[0] {
+            "break" | "b" => {
+                let addr = Self::parse_addr(args.get(1).ok_or("usage: break <addr>")?)?;
+                self.breakpoints.insert(addr);
+                println!("Breakpoint set at x{:04X}", addr);
+            }
+            "clear" => {
+                let addr = Self::parse_addr(args.get(1).ok_or("usage: clear <addr>")?)?;
+                self.breakpoints.remove(&addr);
+                println!("Breakpoint cleared at x{:04X}", addr);
+            }
+            "step" | "s" => {
+                let count:u32 = match args.get(1) {
+                    Some(n) => n.parse().map_err(|_| "invalid step count".to_string())?,
+                    None => 1,
+                };
+                self.step_n(vm, count);
+                self.repeat = count.saturating_sub(1);
+            }
+            "continue" | "c" => {
+                self.continue_until_breakpoint(vm);
+            }
+            "trace" => {
+                self.trace_only = !self.trace_only;
+                println!("trace mode: {}", if self.trace_only {"on"} else {"off"});
+            }
+            "regs" | "r" => self.dump_registers(vm),
+            "mem" | "m" => {
+                let start = Self::parse_addr(args.get(1).ok_or("usage: mem <start> [end]")?)?;
+                let end = match args.get(2) {
+                    Some(a) => Self::parse_addr(a)?,
+                    None => start.saturating_add(16),
+                };
+                self.dump_memory(vm, start, end);
+            }
+            "help" | "h" => Self::print_help(),
+            "quit" | "q" => return Ok(false),
+            other => return Err(format!("unknown command: {}", other)),
+        }
+
+        if args[0] != "step" {
+            self.last_command = Some(args[0].to_string());
+        } else {
+            self.last_command = Some("step".to_string());
+        }
+
+        Ok(true)
+    }
+
+    fn repeat_last(&mut self, vm: &mut Vm, command: &str) -> Result<bool, String> {
+        match command {
+            "step" if self.repeat > 0 => {
+                self.step_n(vm, 1);
+                self.repeat -= 1;
+            }
+            "step" => {}
+            "continue" => self.continue_until_breakpoint(vm),
+            _ => {}
+        }
+        Ok(true)
+    }
+
+    fn step_n(&mut self, vm: &mut Vm, count: u32) {
+        for _ in 0..count {
+            if !self.step_once(vm) {
+                break;
+            }
+        }
+    }
+
+    fn continue_until_breakpoint(&mut self, vm: &mut Vm) {
+        while self.step_once(vm) {}
+    }
+
+    /// Executes a single instruction, printing a trace line in trace mode and stopping at
+    /// breakpoints or errors. Returns `false` when execution should stop.
+    fn step_once(&mut self, vm: &mut Vm) -> bool {
+        let pc_before = vm.reg[Registers::PC as usize];
+        let instruction = match vm.mem_read(pc_before) {
+            Ok(instruction) => instruction,
+            Err(e) => {
+                println!("Stopped at x{:04X}: {}", pc_before, e);
+                return false;
+            }
+        };
+        let regs_before = vm.reg;
+
+        let outcome = match vm.step() {
+            Ok(outcome) => outcome,
+            Err(e) => {
+                println!("Stopped at x{:04X}: {}", pc_before, e);
+                return false;
+            }
+        };
+
+        if self.trace_only {
+            println!("x{:04X}: x{:04X}  {}", pc_before, instruction, disasm(instruction, pc_before.wrapping_add(1)));
+            self.print_register_deltas(&regs_before, &vm.reg);
+        }
+
+        if outcome == StepOutcome::Halt {
+            println!("Halted");
+            return false;
+        }
+
+        if self.breakpoints.contains(&vm.reg[Registers::PC as usize]) {
+            println!("Breakpoint hit at x{:04X}", vm.reg[Registers::PC as usize]);
+            return false;
+        }
+
+        true
+    }
+
+    fn print_register_deltas(&self, before: &[u16], after: &[u16]) {
+        for i in 0..Registers::COUNT as usize {
+            if before[i] != after[i] {
+                println!("  r{} x{:04X} -> x{:04X}", i, before[i], after[i]);
+            }
+        }
+    }
+
+    fn dump_registers(&self, vm: &Vm) {
+        for i in 0..=Registers::R7 as usize {
+            println!("R{}: x{:04X}", i, vm.reg[i]);
+        }
+        println!("PC: x{:04X}", vm.reg[Registers::PC as usize]);
+        let cond = vm.reg[Registers::COND as usize];
+        println!("COND: x{:04X} (N={} Z={} P={})",
+            cond,
+            (cond & 0x4 != 0) as u8,
+            (cond & 0x2 != 0) as u8,
+            (cond & 0x1 != 0) as u8);
+        println!("PSR: x{:04X} (privilege={} priority={})",
+            vm.psr,
+            if vm.psr & 0x8000 != 0 {"user"} else {"supervisor"},
+            (vm.psr >> 8) & 0x7);
+    }
+
+    fn dump_memory(&self, vm: &Vm, start: u16, end: u16) {
+        let mut addr = start;
+        while addr <= end {
+            println!("x{:04X}: x{:04X}", addr, vm.peek_mem(addr));
+            if addr == 0xFFFF {
+                break;
+            }
+            addr += 1;
+        }
+    }
+
+    fn print_help() {
+        println!("break <addr>        set a breakpoint");
+        println!("clear <addr>        clear a breakpoint");
+        println!("step [n]            execute n instructions (default 1)");
+        println!("continue            run until a breakpoint or halt");
+        println!("trace               toggle instruction trace mode");
+        println!("regs                dump all registers and COND flags");
+        println!("mem <start> [end]   hex-dump a memory range");
+        println!("quit                exit the debugger");
+    }
+
+    fn parse_addr(s: &str) -> Result<u16, String> {
+        let s = s.trim_start_matches('x').trim_start_matches("0x");
+        u16::from_str_radix(s, 16).map_err(|_| format!("invalid address: {}", s))
+    }
+}
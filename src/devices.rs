@@ -0,0 +1,199 @@
+use std::io::Read;
+
+use crate::VmError;
+
+/// A device occupying some slice of the address space. `mem_read`/`mem_write`
+/// consult every attached device before falling back to plain memory, so a
+/// device can intercept its registers without the VM special-casing their
+/// addresses the way the old KBSR/KBDR handling did.
+pub trait MemoryMappedDevice {
+    /// Reads a memory-mapped register, if `address` belongs to this device.
+    /// `None` means the address is not claimed by this device; `Some(Err)`
+    /// means it is claimed but the read failed (e.g. an I/O error).
+    fn read(&mut self, address:u16) -> Option<Result<u16,VmError>>;
+    /// Writes a memory-mapped register, if `address` belongs to this device.
+    /// Returns whether `address` belonged to this device.
+    fn write(&mut self, address:u16, value:u16) -> bool;
+    /// Non-mutating lookup of a register's current value, for inspection
+    /// tools (the debugger's `mem` dump) that must not trigger side effects
+    /// like `read`'s blocking stdin poll.
+    fn peek(&self, address:u16) -> Option<u16>;
+    /// Advances the device by one instruction cycle. Most devices are purely
+    /// reactive and don't need this; `Display` uses it to refresh its window
+    /// on a timer instead of only when the running program writes `FB_CTRL`.
+    fn tick(&mut self) {}
+}
+
+pub const KBSR:u16 = 0xFE00; //keyboard status
+pub const KBDR:u16 = 0xFE02; //keyboard data
+
+/// Memory-mapped keyboard. Reading `KBSR` blocks on stdin for a single byte
+/// and latches it into `KBDR`, matching the original busy-wait polling loop
+/// LC-3 programs use to read a character.
+pub struct Keyboard {
+    ready:bool,
+    last_key:u16,
+}
+
+impl Keyboard {
+    pub fn new() -> Keyboard {
+        Keyboard { ready:false, last_key:0 }
+    }
+
+    fn poll(&mut self) -> Result<(),VmError> {
+        let mut buffer = [0u8;1];
+        std::io::stdin().read_exact(&mut buffer)?;
+        if buffer[0] != 0 {
+            self.ready = true;
+            self.last_key = buffer[0] as u16;
+        } else {
+            self.ready = false;
+        }
+        Ok(())
+    }
+}
+
+impl MemoryMappedDevice for Keyboard {
+    fn read(&mut self, address:u16) -> Option<Result<u16,VmError>> {
+        match address {
+            KBSR => Some(self.poll().map(|_| if self.ready { 1 << 15 } else { 0 })),
+            KBDR => Some(Ok(self.last_key)),
+            _ => None,
+        }
+    }
+
+    fn write(&mut self, _address:u16, _value:u16) -> bool {
+        false
+    }
+
+    fn peek(&self, address:u16) -> Option<u16> {
+        match address {
+            KBSR => Some(if self.ready { 1 << 15 } else { 0 }),
+            KBDR => Some(self.last_key),
+            _ => None,
+        }
+    }
+}
+
+pub const FB_BASE:u16 = 0xC000;
+pub const FB_WIDTH:usize = 64;
+pub const FB_HEIGHT:usize = 32;
+const FB_SIZE:usize = FB_WIDTH * FB_HEIGHT;
+/// Writing any value here asks the device to present the current buffer.
+pub const FB_CTRL:u16 = 0xFE10;
+/// Ticks (instructions executed) between automatic refreshes, so the window
+/// stays live even if the running program never writes `FB_CTRL` itself.
+const FB_REFRESH_INTERVAL:u32 = 10_000;
+
+/// Memory-mapped framebuffer. Each of `FB_BASE..FB_BASE+FB_SIZE` holds one
+/// pixel (non-zero = lit); writing `FB_CTRL` presents the buffer immediately,
+/// and it is also presented automatically every `FB_REFRESH_INTERVAL` ticks.
+/// Headless builds just keep the buffer around for `mem` inspection;
+/// with the `sdl` feature enabled, presenting blits it to an actual window.
+pub struct Display {
+    buffer:[u16;FB_SIZE],
+    ticks_since_refresh:u32,
+    #[cfg(feature = "sdl")]
+    window:Option<SdlWindow>,
+}
+
+impl Display {
+    pub fn new() -> Display {
+        Display {
+            buffer:[0;FB_SIZE],
+            ticks_since_refresh:0,
+            #[cfg(feature = "sdl")]
+            window:SdlWindow::try_new().ok(),
+        }
+    }
+
+    fn present(&mut self) {
+        #[cfg(feature = "sdl")]
+        if let Some(window) = self.window.as_mut() {
+            window.blit(&self.buffer);
+        }
+    }
+}
+
+impl MemoryMappedDevice for Display {
+    fn read(&mut self, address:u16) -> Option<Result<u16,VmError>> {
+        if (FB_BASE..FB_BASE + FB_SIZE as u16).contains(&address) {
+            Some(Ok(self.buffer[(address - FB_BASE) as usize]))
+        } else {
+            None
+        }
+    }
+
+    fn write(&mut self, address:u16, value:u16) -> bool {
+        if (FB_BASE..FB_BASE + FB_SIZE as u16).contains(&address) {
+            self.buffer[(address - FB_BASE) as usize] = value;
+            true
+        } else if address == FB_CTRL {
+            self.present();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn peek(&self, address:u16) -> Option<u16> {
+        if (FB_BASE..FB_BASE + FB_SIZE as u16).contains(&address) {
+            Some(self.buffer[(address - FB_BASE) as usize])
+        } else {
+            None
+        }
+    }
+
+    fn tick(&mut self) {
+        self.ticks_since_refresh += 1;
+        if self.ticks_since_refresh >= FB_REFRESH_INTERVAL {
+            self.ticks_since_refresh = 0;
+            self.present();
+        }
+    }
+}
+
+#[cfg(feature = "sdl")]
+struct SdlWindow {
+    canvas:sdl2::render::Canvas<sdl2::video::Window>,
+}
+
+#[cfg(feature = "sdl")]
+impl SdlWindow {
+    const SCALE:u32 = 8;
+
+    fn try_new() -> Result<SdlWindow,String> {
+        let sdl_context = sdl2::init()?;
+        let video = sdl_context.video()?;
+        let window = video
+            .window("LC-3 display", FB_WIDTH as u32 * Self::SCALE, FB_HEIGHT as u32 * Self::SCALE)
+            .position_centered()
+            .build()
+            .map_err(|e| e.to_string())?;
+        let canvas = window.into_canvas().build().map_err(|e| e.to_string())?;
+        Ok(SdlWindow { canvas })
+    }
+
+    fn blit(&mut self, buffer:&[u16;FB_SIZE]) {
+        use sdl2::pixels::Color;
+        use sdl2::rect::Rect;
+
+        self.canvas.set_draw_color(Color::BLACK);
+        self.canvas.clear();
+        self.canvas.set_draw_color(Color::WHITE);
+        for y in 0..FB_HEIGHT {
+            for x in 0..FB_WIDTH {
+                if buffer[y * FB_WIDTH + x] != 0 {
+                    let rect = Rect::new(
+                        (x as u32 * Self::SCALE) as i32,
+                        (y as u32 * Self::SCALE) as i32,
+                        Self::SCALE,
+                        Self::SCALE,
+                    );
+                    let _ = self.canvas.fill_rect(rect);
+                }
+            }
+        }
+        self.canvas.present();
+    }
+}
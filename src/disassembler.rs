@@ -0,0 +1,115 @@
+use crate::{Operators, Vm, TRAP};
+
+/// Disassembles a single instruction word into its LC-3 mnemonic form.
+/// `pc` is the address of the instruction *after* `instruction` (the value
+/// the real PC holds once it has been fetched), used to resolve PC-relative
+/// offsets to absolute addresses.
+pub fn disasm(instruction: u16, pc: u16) -> String {
+    match Operators::from(instruction >> 12) {
+        Ok(Operators::BR) => disasm_br(instruction, pc),
+        Ok(Operators::ADD) => disasm_add(instruction),
+        Ok(Operators::LD) => disasm_ld_st("LD", instruction, pc),
+        Ok(Operators::ST) => disasm_ld_st("ST", instruction, pc),
+        Ok(Operators::JSR) => disasm_jsr(instruction, pc),
+        Ok(Operators::AND) => disasm_and(instruction),
+        Ok(Operators::LDR) => disasm_ldr_str("LDR", instruction),
+        Ok(Operators::STR) => disasm_ldr_str("STR", instruction),
+        Ok(Operators::RTI) => "RTI".to_string(),
+        Ok(Operators::NOT) => disasm_not(instruction),
+        Ok(Operators::LDI) => disasm_ld_st("LDI", instruction, pc),
+        Ok(Operators::STI) => disasm_ld_st("STI", instruction, pc),
+        Ok(Operators::JMP) => disasm_jmp(instruction),
+        Ok(Operators::RES) => "RES".to_string(),
+        Ok(Operators::LEA) => disasm_ld_st("LEA", instruction, pc),
+        Ok(Operators::TRAP) => disasm_trap(instruction),
+        Err(_) => format!(".FILL x{:04X}", instruction),
+    }
+}
+
+fn disasm_br(instruction: u16, pc: u16) -> String {
+    let pc_offset = Vm::sign_extend(instruction & 0x1FF, 9);
+    let target = (pc as u32 + pc_offset as u32) as u16;
+    let cond = (instruction >> 9) & 0x7;
+    let n = if cond & 0x4 != 0 {"n"} else {""};
+    let z = if cond & 0x2 != 0 {"z"} else {""};
+    let p = if cond & 0x1 != 0 {"p"} else {""};
+    format!("BR{}{}{} x{:04X}", n, z, p, target)
+}
+
+fn disasm_add(instruction: u16) -> String {
+    let dest = (instruction >> 9) & 0x7;
+    let op1 = (instruction >> 6) & 0x7;
+    if (instruction >> 5) & 0x1 == 1 {
+        let imm = Vm::sign_extend(instruction & 0x1F, 5) as i16;
+        format!("ADD R{}, R{}, #{}", dest, op1, imm)
+    } else {
+        let op2 = instruction & 0x7;
+        format!("ADD R{}, R{}, R{}", dest, op1, op2)
+    }
+}
+
+fn disasm_and(instruction: u16) -> String {
+    let dest = (instruction >> 9) & 0x7;
+    let op1 = (instruction >> 6) & 0x7;
+    if (instruction >> 5) & 0x1 == 1 {
+        let imm = Vm::sign_extend(instruction & 0x1F, 5) as i16;
+        format!("AND R{}, R{}, #{}", dest, op1, imm)
+    } else {
+        let op2 = instruction & 0x7;
+        format!("AND R{}, R{}, R{}", dest, op1, op2)
+    }
+}
+
+fn disasm_not(instruction: u16) -> String {
+    let dest = (instruction >> 9) & 0x7;
+    let src = (instruction >> 6) & 0x7;
+    format!("NOT R{}, R{}", dest, src)
+}
+
+fn disasm_ld_st(mnemonic: &str, instruction: u16, pc: u16) -> String {
+    let r0 = (instruction >> 9) & 0x7;
+    let pc_offset = Vm::sign_extend(instruction & 0x1FF, 9);
+    let target = (pc as u32 + pc_offset as u32) as u16;
+    format!("{} R{}, x{:04X}", mnemonic, r0, target)
+}
+
+fn disasm_ldr_str(mnemonic: &str, instruction: u16) -> String {
+    let r0 = (instruction >> 9) & 0x7;
+    let r1 = (instruction >> 6) & 0x7;
+    let offset = Vm::sign_extend(instruction & 0x3F, 6) as i16;
+    format!("{} R{}, R{}, #{}", mnemonic, r0, r1, offset)
+}
+
+fn disasm_jmp(instruction: u16) -> String {
+    let r1 = (instruction >> 6) & 0x7;
+    if r1 == 7 {
+        "RET".to_string()
+    } else {
+        format!("JMP R{}", r1)
+    }
+}
+
+fn disasm_jsr(instruction: u16, pc: u16) -> String {
+    if (instruction >> 11) & 1 != 0 {
+        let pc_offset = Vm::sign_extend(instruction & 0x7FF, 11);
+        let target = (pc as u32 + pc_offset as u32) as u16;
+        format!("JSR x{:04X}", target)
+    } else {
+        let r1 = (instruction >> 6) & 0x7;
+        format!("JSRR R{}", r1)
+    }
+}
+
+fn disasm_trap(instruction: u16) -> String {
+    let vect = instruction & 0xFF;
+    let name = match TRAP::from(vect) {
+        Ok(TRAP::GETC) => " (GETC)",
+        Ok(TRAP::OUT) => " (OUT)",
+        Ok(TRAP::PUTS) => " (PUTS)",
+        Ok(TRAP::IN) => " (IN)",
+        Ok(TRAP::PUTSP) => " (PUTSP)",
+        Ok(TRAP::HALT) => " (HALT)",
+        Err(_) => "",
+    };
+    format!("TRAP x{:02X}{}", vect, name)
+}
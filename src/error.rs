@@ -0,0 +1,34 @@
+use std::fmt;
+use std::io;
+
+/// Errors that can occur while loading an image or executing instructions.
+/// Replaces the old `.expect()`/`.unwrap()`/`exit()` calls so the VM can be
+/// embedded in a debugger or test harness instead of killing the process.
+#[derive(Debug)]
+pub enum VmError {
+    Io(io::Error),
+    BadImage(String),
+    IllegalOpcode(u16),
+    UnknownTrap(u16),
+    PrivilegeViolation,
+}
+
+impl fmt::Display for VmError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            VmError::Io(e) => write!(f, "i/o error: {}", e),
+            VmError::BadImage(msg) => write!(f, "bad image: {}", msg),
+            VmError::IllegalOpcode(instruction) => write!(f, "illegal opcode: x{:04X}", instruction),
+            VmError::UnknownTrap(vect) => write!(f, "unknown trap vector: x{:02X}", vect),
+            VmError::PrivilegeViolation => write!(f, "privilege mode violation: RTI executed in user mode"),
+        }
+    }
+}
+
+impl std::error::Error for VmError {}
+
+impl From<io::Error> for VmError {
+    fn from(e: io::Error) -> Self {
+        VmError::Io(e)
+    }
+}
@@ -0,0 +1,103 @@
+use crate::devices::MemoryMappedDevice;
+
+/// A device capable of raising a prioritized interrupt, on top of the
+/// memory-mapped registers every device exposes. Lets a device (timer,
+/// keyboard, ...) be polled and serviced the same way regardless of what
+/// it models.
+pub trait InterruptDevice: MemoryMappedDevice {
+    /// Advances the device's internal state by one instruction cycle.
+    fn tick(&mut self);
+    /// Whether the device currently has an interrupt pending.
+    fn interrupt_pending(&self) -> bool;
+    /// Clears the device's pending interrupt once it has been serviced.
+    fn clear_interrupt(&mut self);
+    /// Interrupt priority level (0-7); only interrupts above the current
+    /// PSR priority are serviced.
+    fn priority(&self) -> u8;
+    /// Index into the interrupt vector table at x0100-x01FF.
+    fn vector(&self) -> u8;
+}
+
+pub const TIMER_STATUS:u16 = 0xFE04;   // bit 15: enable, bit 0: interrupt pending
+pub const TIMER_INTERVAL:u16 = 0xFE06; // ticks between interrupts
+
+/// A free-running timer that raises an interrupt every `interval` ticks
+/// while enabled.
+pub struct Timer {
+    enabled: bool,
+    interval: u16,
+    counter: u16,
+    pending: bool,
+}
+
+impl Timer {
+    pub fn new() -> Timer {
+        Timer {
+            enabled: false,
+            interval: 0,
+            counter: 0,
+            pending: false,
+        }
+    }
+}
+
+impl InterruptDevice for Timer {
+    fn tick(&mut self) {
+        if !self.enabled || self.interval == 0 {
+            return;
+        }
+        self.counter += 1;
+        if self.counter >= self.interval {
+            self.counter = 0;
+            self.pending = true;
+        }
+    }
+
+    fn interrupt_pending(&self) -> bool {
+        self.pending
+    }
+
+    fn clear_interrupt(&mut self) {
+        self.pending = false;
+    }
+
+    fn priority(&self) -> u8 {
+        4
+    }
+
+    fn vector(&self) -> u8 {
+        0x40
+    }
+}
+
+impl MemoryMappedDevice for Timer {
+    fn read(&mut self, address: u16) -> Option<Result<u16,crate::VmError>> {
+        match address {
+            TIMER_STATUS => Some(Ok(((self.enabled as u16) << 15) | self.pending as u16)),
+            TIMER_INTERVAL => Some(Ok(self.interval)),
+            _ => None,
+        }
+    }
+
+    fn write(&mut self, address: u16, value: u16) -> bool {
+        match address {
+            TIMER_STATUS => {
+                self.enabled = value & (1 << 15) != 0;
+                true
+            }
+            TIMER_INTERVAL => {
+                self.interval = value;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn peek(&self, address: u16) -> Option<u16> {
+        match address {
+            TIMER_STATUS => Some(((self.enabled as u16) << 15) | self.pending as u16),
+            TIMER_INTERVAL => Some(self.interval),
+            _ => None,
+        }
+    }
+}
@@ -4,6 +4,19 @@ use std::process::exit;
 use std::fs::File;
 use std::io::{Read, Write, BufReader};
 
+mod assembler;
+mod debugger;
+mod devices;
+mod disassembler;
+mod error;
+mod interrupts;
+
+use debugger::Debugger;
+use devices::{Display, Keyboard, MemoryMappedDevice};
+use disassembler::disasm;
+pub use error::VmError;
+use interrupts::{InterruptDevice, Timer};
+
 
 pub enum Registers {
     R0 = 0,
@@ -38,7 +51,7 @@ pub enum Operators {
     TRAP    /* execute trap */
 }
 impl Operators {
-    fn from(val:u16) -> Result<Operators,i16> {
+    pub(crate) fn from(val:u16) -> Result<Operators,i16> {
         match val {
             0 =>  {return Ok(Operators::BR)},
             1 =>  {return Ok(Operators::ADD)},
@@ -74,7 +87,7 @@ pub enum TRAP {
     HALT = 0x25   //halts the program
 }
 impl TRAP {
-    fn from(val:u16) -> Result<TRAP,i16> {
+    pub(crate) fn from(val:u16) -> Result<TRAP,i16> {
         match val {
             0x20 =>  {return Ok(TRAP::GETC)},
             0x21 =>  {return Ok(TRAP::OUT)},
@@ -86,17 +99,22 @@ impl TRAP {
         }   
     }   
 }
-pub enum MR {
-    KBSR = 0xFE00, //keyboard status
-    KBDR = 0xFE02  //keyboard data
-}
-
 const MEM_MAX:usize = 1 << 16;
 
 
-struct Vm {
+pub struct Vm {
     pub reg:[u16;Registers::COUNT as usize],
-    pub mem:[u16;MEM_MAX]
+    pub mem:[u16;MEM_MAX],
+    /// Processor status register: bit 15 is the privilege bit (1 = user,
+    /// 0 = supervisor), bits 10-8 hold the current priority, bits 2-0
+    /// mirror `reg[COND]`.
+    pub psr:u16,
+    saved_ssp:u16,
+    saved_usp:u16,
+    interrupt_devices:Vec<Box<dyn InterruptDevice>>,
+    /// Plain memory-mapped devices (no interrupt capability), consulted by
+    /// `mem_read`/`mem_write` the same way `interrupt_devices` are.
+    mmio_devices:Vec<Box<dyn MemoryMappedDevice>>,
 }
 
 
@@ -104,7 +122,12 @@ impl Vm {
     pub fn new() -> Vm {
         Vm {
             reg:[0;Registers::COUNT as usize],
-            mem:[0;MEM_MAX]
+            mem:[0;MEM_MAX],
+            psr: 0x8000,
+            saved_ssp: 0x3000,
+            saved_usp: 0,
+            interrupt_devices: vec![Box::new(Timer::new())],
+            mmio_devices: vec![Box::new(Keyboard::new()), Box::new(Display::new())],
         }
     }
     #[inline]
@@ -115,11 +138,13 @@ impl Vm {
         return x
     }
     #[inline]
-    fn swap16(x:u16) -> u16 {
+    pub(crate) fn swap16(x:u16) -> u16 {
         (x << 8) | (x >> 8)
     }
-    pub fn read_image(&mut self,file:&str)-> std::io::Result<usize> {
-        let f = File::open(file).expect("Couldn't open file");
+    /// Loads a big-endian `.obj` image into memory. Returns the origin
+    /// address the image was loaded at, along with the number of bytes read.
+    pub fn read_image(&mut self,file:&str)-> Result<(u16,usize),VmError> {
+        let f = File::open(file)?;
 
         let f = BufReader::new(f);
 
@@ -133,20 +158,20 @@ impl Vm {
         origin = Self::swap16(origin);
 
         if origin as usize > MEM_MAX {
-            return Result::Err(std::io::Error::new(std::io::ErrorKind::Other,"Origin larger than MEM_MAX"));
+            return Err(VmError::BadImage("origin larger than MEM_MAX".to_string()));
         }
-        
+
         let ptr = unsafe {std::slice::from_raw_parts_mut(&mut self.mem[origin as usize] as *mut u16 as *mut u8 , MEM_MAX as usize*2-origin as usize)};
-        
-        let result = handle.read(ptr).unwrap();
+
+        let result = handle.read(ptr)?;
         let mut i = 0;
         while i < (result as f64/2.0+0.5) as usize {
             self.mem[origin as usize + i] = Self::swap16(self.mem[origin as usize + i]);
             i+=1;
         }
-        
 
-        Result::Ok(result)
+
+        Ok((origin,result))
     }
     #[inline]
     pub fn update_flags(&mut self,register:u16) {
@@ -157,30 +182,148 @@ impl Vm {
         } else {
             self.reg[Registers::COND as usize] = Flags::POS as u16;
         }
+        self.psr = (self.psr & !0x7) | self.reg[Registers::COND as usize];
     }
+    /// Dispatches a write to whichever device (if any) claims `address`,
+    /// falling back to plain memory. Replaces the old ad-hoc KBSR/KBDR
+    /// special-casing with a uniform device-dispatch layer.
     #[inline]
-    pub fn mem_write(&mut self,address:u16,val:u16) {
+    pub fn mem_write(&mut self,address:u16,val:u16) -> Result<(),VmError> {
+        for device in self.interrupt_devices.iter_mut() {
+            if device.write(address,val) {
+                return Ok(());
+            }
+        }
+        for device in self.mmio_devices.iter_mut() {
+            if device.write(address,val) {
+                return Ok(());
+            }
+        }
         self.mem[address as usize] = val;
+        Ok(())
     }
+    /// Dispatches a read to whichever device (if any) claims `address`,
+    /// falling back to plain memory.
     #[inline]
-    pub fn mem_read(&mut self,address:u16) -> u16 {
-        if address == MR::KBSR as u16 {
-            self.handle_keyboard();
+    pub fn mem_read(&mut self,address:u16) -> Result<u16,VmError> {
+        for device in self.interrupt_devices.iter_mut() {
+            if let Some(result) = device.read(address) {
+                return result;
+            }
+        }
+        for device in self.mmio_devices.iter_mut() {
+            if let Some(result) = device.read(address) {
+                return result;
+            }
         }
 
+        Ok(self.mem[address as usize])
+
+    }
+    /// Looks up `address` for inspection (the debugger's `mem` dump) without
+    /// triggering device side effects such as blocking on stdin.
+    #[inline]
+    pub fn peek_mem(&self, address:u16) -> u16 {
+        for device in self.interrupt_devices.iter() {
+            if let Some(val) = device.peek(address) {
+                return val;
+            }
+        }
+        for device in self.mmio_devices.iter() {
+            if let Some(val) = device.peek(address) {
+                return val;
+            }
+        }
         self.mem[address as usize]
+    }
+    /// Advances every attached device by one instruction cycle.
+    #[inline]
+    pub fn tick_devices(&mut self) {
+        for device in self.interrupt_devices.iter_mut() {
+            InterruptDevice::tick(device.as_mut());
+        }
+        for device in self.mmio_devices.iter_mut() {
+            device.tick();
+        }
+    }
+    /// Services the highest-priority pending interrupt above the current
+    /// PSR priority, if any, by vectoring through x0100-x01FF.
+    fn service_interrupts(&mut self) -> Result<(),VmError> {
+        let current_priority = ((self.psr >> 8) & 0x7) as u8;
+        let mut best:Option<usize> = None;
+        for (i,device) in self.interrupt_devices.iter().enumerate() {
+            if device.interrupt_pending() && device.priority() > current_priority {
+                let better = match best {
+                    Some(b) => device.priority() > self.interrupt_devices[b].priority(),
+                    None => true,
+                };
+                if better {
+                    best = Some(i);
+                }
+            }
+        }
+        if let Some(i) = best {
+            let vector = self.interrupt_devices[i].vector();
+            let priority = self.interrupt_devices[i].priority();
+            self.interrupt_devices[i].clear_interrupt();
+            self.interrupt_entry(vector,priority)?;
+        }
+        Ok(())
+    }
+    /// Pushes PSR and PC onto the supervisor stack, switching onto it first
+    /// if the interrupted code was running in user mode, then vectors
+    /// through the interrupt vector table.
+    fn interrupt_entry(&mut self, vector:u8, priority:u8) -> Result<(),VmError> {
+        let pc = self.reg[Registers::PC as usize];
+        self.push_supervisor_frame(pc,priority)?;
+        self.reg[Registers::PC as usize] = self.mem_read(0x0100 + vector as u16)?;
+        Ok(())
+    }
+    /// Pushes PSR and `pc` onto the supervisor stack, switching onto it
+    /// first if the current code is running in user mode, then raises
+    /// privilege to supervisor at `priority`. Shared by hardware interrupt
+    /// entry and `TRAP` entry.
+    fn push_supervisor_frame(&mut self, pc:u16, priority:u8) -> Result<(),VmError> {
+        if self.psr & 0x8000 != 0 {
+            self.saved_usp = self.reg[Registers::R6 as usize];
+            self.reg[Registers::R6 as usize] = self.saved_ssp;
+        }
 
+        let psr = self.psr;
+        self.reg[Registers::R6 as usize] -= 1;
+        self.mem_write(self.reg[Registers::R6 as usize],psr)?;
+        self.reg[Registers::R6 as usize] -= 1;
+        self.mem_write(self.reg[Registers::R6 as usize],pc)?;
+
+        self.psr = ((priority as u16) << 8) | (self.psr & 0x7);
+        Ok(())
+    }
+    /// Pops PC and PSR off the supervisor stack and restores the user stack
+    /// if the popped PSR says so. Shared by `rti` and `TRAP` return.
+    fn pop_supervisor_frame(&mut self) -> Result<(),VmError> {
+        let pc = self.mem_read(self.reg[Registers::R6 as usize])?;
+        self.reg[Registers::R6 as usize] += 1;
+        let psr = self.mem_read(self.reg[Registers::R6 as usize])?;
+        self.reg[Registers::R6 as usize] += 1;
+
+        self.reg[Registers::PC as usize] = pc;
+        self.psr = psr;
+        self.reg[Registers::COND as usize] = psr & 0x7;
+
+        if self.psr & 0x8000 != 0 {
+            self.saved_ssp = self.reg[Registers::R6 as usize];
+            self.reg[Registers::R6 as usize] = self.saved_usp;
+        }
+        Ok(())
     }
+    /// Returns from an interrupt: pops PC and PSR off the
+    /// supervisor stack and restores the user stack if PSR says so.
     #[inline]
-    fn handle_keyboard(&mut self) {
-        let mut buffer = [0; 1];
-        std::io::stdin().read_exact(&mut buffer).unwrap();
-        if buffer[0] != 0 {
-            self.mem[MR::KBSR as usize] = 1 << 15;
-            self.mem[MR::KBDR as usize] = buffer[0] as u16;
-        } else {
-            self.mem[MR::KBSR as usize] = 0;
+    pub fn rti(&mut self) -> Result<(),VmError> {
+        if self.psr & 0x8000 != 0 {
+            return Err(VmError::PrivilegeViolation);
         }
+        self.pop_supervisor_frame()
     }
     #[inline]
     pub fn br(&mut self,instruction:u16) {
@@ -206,17 +349,18 @@ impl Vm {
         self.update_flags(dest);
     }
     #[inline]
-    pub fn ld(&mut self, instruction:u16) {
+    pub fn ld(&mut self, instruction:u16) -> Result<(),VmError> {
         let r0:u16 = (instruction >> 9) & 0x7;
         let pc_offset:u16 = Vm::sign_extend(instruction & 0x1ff, 9);
-        self.reg[r0 as usize] = self.mem_read((self.reg[Registers::PC as usize] as u32 + pc_offset as u32) as u16);
+        self.reg[r0 as usize] = self.mem_read((self.reg[Registers::PC as usize] as u32 + pc_offset as u32) as u16)?;
         self.update_flags(r0);
+        Ok(())
     }
     #[inline]
-    pub fn st(&mut self, instruction:u16) {
+    pub fn st(&mut self, instruction:u16) -> Result<(),VmError> {
         let r0:u16 = (instruction >> 9) & 0x7;
         let pc_offset:u16 = Vm::sign_extend(instruction & 0x1FF, 9);
-        self.mem_write((self.reg[Registers::PC as usize] as u32+pc_offset as u32) as u16, self.reg[r0 as usize]);
+        self.mem_write((self.reg[Registers::PC as usize] as u32+pc_offset as u32) as u16, self.reg[r0 as usize])
     }
     #[inline]
     pub fn jsr(&mut self, instruction:u16) {
@@ -248,19 +392,20 @@ impl Vm {
         self.update_flags(r0);
     }
     #[inline]
-    pub fn ldr(&mut self, instruction:u16) {
+    pub fn ldr(&mut self, instruction:u16) -> Result<(),VmError> {
         let r0:u16 = (instruction >> 9) & 0x7;
         let r1:u16 = (instruction >> 6) & 0x7;
         let offset:u16 = Vm::sign_extend(instruction & 0x3F, 6);
-        self.reg[r0 as usize] = self.mem_read(self.reg[r1 as usize] + offset).clone();
+        self.reg[r0 as usize] = self.mem_read(self.reg[r1 as usize] + offset)?;
         self.update_flags(r0);
+        Ok(())
     }
     #[inline]
-    pub fn str(&mut self, instruction:u16) {
+    pub fn str(&mut self, instruction:u16) -> Result<(),VmError> {
         let r0:u16 = (instruction >> 9) & 0x7;
         let r1:u16 = (instruction >> 6) & 0x7;
         let offset:u16 = Vm::sign_extend(instruction & 0x3F,6);
-        self.mem_write((self.reg[r1 as usize] as u32 + offset as u32) as u16,self.reg[r0 as usize]);
+        self.mem_write((self.reg[r1 as usize] as u32 + offset as u32) as u16,self.reg[r0 as usize])
     }
     #[inline]
     pub fn not(&mut self, instruction:u16) {
@@ -270,19 +415,20 @@ impl Vm {
         self.update_flags(r0);
     }
     #[inline]
-    pub fn ldi(&mut self, instruction:u16) {
+    pub fn ldi(&mut self, instruction:u16) -> Result<(),VmError> {
         let r0 = (instruction >> 9) & 0x7;
         let pc_offset = Vm::sign_extend(instruction & 0x1ff,9);
-        let temp = self.mem_read(self.reg[Registers::PC as usize]+pc_offset);
-        self.reg[r0 as usize] = self.mem_read(temp); 
+        let temp = self.mem_read(self.reg[Registers::PC as usize]+pc_offset)?;
+        self.reg[r0 as usize] = self.mem_read(temp)?;
         self.update_flags(r0);
+        Ok(())
     }
     #[inline]
-    pub fn sti(&mut self, instruction:u16) {
+    pub fn sti(&mut self, instruction:u16) -> Result<(),VmError> {
         let r0:u16 = (instruction >> 9) & 0x7;
         let pc_offset = Vm::sign_extend(instruction & 0x1FF, 9);
-        let temp = self.mem_read((self.reg[Registers::PC as usize] as u32+pc_offset as u32) as u16);
-        self.mem_write(temp,self.reg[r0 as usize]);
+        let temp = self.mem_read((self.reg[Registers::PC as usize] as u32+pc_offset as u32) as u16)?;
+        self.mem_write(temp,self.reg[r0 as usize])
     }
     #[inline]
     pub fn jmp(&mut self, instruction:u16) {
@@ -296,43 +442,53 @@ impl Vm {
         self.reg[r0 as usize] = (self.reg[Registers::PC as usize] as u32 + pc_offset as u32) as u16;
         self.update_flags(r0);
     }
+    /// Executes a TRAP. Trap service routines run in supervisor mode, so
+    /// entry pushes PSR and PC onto the supervisor stack (switching onto it
+    /// first if necessary) the same way a hardware interrupt does; the
+    /// frame is popped back before returning, restoring the caller's
+    /// privilege and stack.
     #[inline]
-    pub fn trap(&mut self, instruction:u16) {
+    pub fn trap(&mut self, instruction:u16) -> Result<StepOutcome,VmError> {
+        let vect = instruction & 0xFF;
+        let trap = TRAP::from(vect).map_err(|_| VmError::UnknownTrap(vect))?;
+
         self.reg[Registers::R7 as usize] = self.reg[Registers::PC as usize];
-        match TRAP::from(instruction & 0xFF) {
-            Ok(TRAP::GETC) => {
+        self.push_supervisor_frame(self.reg[Registers::PC as usize],0)?;
+
+        match trap {
+            TRAP::GETC => {
                 let mut buffer = [0;1];
-                std::io::stdin().read_exact(&mut buffer).unwrap();
+                std::io::stdin().read_exact(&mut buffer)?;
                 self.reg[Registers::R0 as usize] = buffer[0] as u16;
             },
-            Ok(TRAP::OUT) => {
+            TRAP::OUT => {
                 let c = self.reg[Registers::R0 as usize] as u8;
                 print!("{}",c as char);
             },
-            Ok(TRAP::PUTS) => {
+            TRAP::PUTS => {
                 let mut index = self.reg[Registers::R0 as usize];
-                let mut c = self.mem_read(index);
+                let mut c = self.mem_read(index)?;
                 while c != 0x0000 {
                     print!("{}",(c as u8) as char);
                     index+=1;
-                    c = self.mem_read(index);
+                    c = self.mem_read(index)?;
                 }
-                io::stdout().flush().expect("failed to flush");
+                io::stdout().flush()?;
             },
-            Ok(TRAP::IN) => {
+            TRAP::IN => {
                 print!("Enter a character :");
-                io::stdout().flush().expect("Failed to flush");
+                io::stdout().flush()?;
                 let char = std::io::stdin()
                     .bytes()
                     .next()
                     .and_then(|result| result.ok())
                     .map(|byte| byte as u16)
-                    .unwrap();
+                    .ok_or_else(|| VmError::Io(io::Error::new(io::ErrorKind::UnexpectedEof,"no character available")))?;
                 self.reg[Registers::R0 as usize] = char;
             },
-            Ok(TRAP::PUTSP) => {
+            TRAP::PUTSP => {
                 let mut index = self.reg[Registers::R0 as usize];
-                let mut c = self.mem_read(index);
+                let mut c = self.mem_read(index)?;
                 while c != 0x0000 {
                     let c1 = ((c & 0xFF) as u8) as char;
                     print!("{}",c1);
@@ -341,100 +497,187 @@ impl Vm {
                         print!("{}",c2);
                     }
                     index+=1;
-                    c = self.mem_read(index);
+                    c = self.mem_read(index)?;
                 }
-                io::stdout().flush().expect("failed to flush");
+                io::stdout().flush()?;
             },
-            Ok(TRAP::HALT) => {
+            TRAP::HALT => {
                 println!("Halt");
-                io::stdout().flush().expect("failed to flush");
-                exit(1);
+                io::stdout().flush()?;
+                self.pop_supervisor_frame()?;
+                return Ok(StepOutcome::Halt);
             },
-            _ => {
-                println!("Ended due to invalid instruction");
-
-                exit(1);
-            }
         }
+        self.pop_supervisor_frame()?;
+        Ok(StepOutcome::Continue)
     }
 
-}
-
-pub fn main() {
-    let args: Vec<String> = env::args().collect();
+    /// Fetches, decodes and executes a single instruction at the current PC.
+    /// Returns `StepOutcome::Halt` once `TRAP x25` runs, or `Err` for an
+    /// illegal opcode or any I/O failure, leaving it to the caller to decide
+    /// how to report or recover.
+    pub fn step(&mut self) -> Result<StepOutcome,VmError> {
+        self.tick_devices();
+        self.service_interrupts()?;
 
-    if args.len() < 2 {
-        exit(-2)
-    }
-    
-    let mut vm = Vm::new();
-    vm.reg[Registers::PC as usize] = 0x3000; //PC register to start
-
-    for i in 1..args.len() {
-        let result = vm.read_image(args[i].as_str()).unwrap();
-        if result == 0 {
-            println!("Failed to read from {}",args[i]);
-        } else {
-            println!("Read count: {}",result);
-        }
-    }
-    
-
-    
-    'l: loop {
-        let instruction:u16 = vm.mem_read(vm.reg[Registers::PC as usize]);
-        vm.reg[Registers::PC as usize] += 1;
+        let instruction:u16 = self.mem_read(self.reg[Registers::PC as usize])?;
+        self.reg[Registers::PC as usize] = self.reg[Registers::PC as usize].wrapping_add(1);
         let op = Operators::from(instruction >> 12);
 
         match op {
             Ok(Operators::BR)  =>   {
-                vm.br(instruction);
+                self.br(instruction);
             }
             Ok(Operators::ADD)  =>  {
-                vm.add(instruction);
+                self.add(instruction);
             }
             Ok(Operators::LD)   =>  {
-                vm.ld(instruction);
+                self.ld(instruction)?;
             }
             Ok(Operators::ST)   =>  {
-                vm.st(instruction);
+                self.st(instruction)?;
             }
             Ok(Operators::JSR)  =>  {
-                vm.jsr(instruction);
+                self.jsr(instruction);
             }
             Ok(Operators::AND)  =>  {
-                vm.and(instruction);
+                self.and(instruction);
             }
             Ok(Operators::LDR)  =>  {
-                vm.ldr(instruction);
+                self.ldr(instruction)?;
             }
             Ok(Operators::STR)  =>  {
-                vm.str(instruction);
+                self.str(instruction)?;
+            }
+            Ok(Operators::RTI)  =>  {
+                self.rti()?;
             }
-            Ok(Operators::RTI)  =>  {}
             Ok(Operators::NOT)  =>  {
-                vm.not(instruction);
+                self.not(instruction);
             }
             Ok(Operators::LDI)  =>  {
-                vm.ldi(instruction);
-
+                self.ldi(instruction)?;
             }
             Ok(Operators::STI)  =>  {
-                vm.sti(instruction);
+                self.sti(instruction)?;
             }
             Ok(Operators::JMP)  =>  {
-                vm.jmp(instruction);
+                self.jmp(instruction);
+            }
+            Ok(Operators::RES)  =>  {
+                return Err(VmError::IllegalOpcode(instruction));
             }
-            Ok(Operators::RES)  =>  {}
             Ok(Operators::LEA)  =>  {
-                vm.lea(instruction);
+                self.lea(instruction);
             }
             Ok(Operators::TRAP) =>  {
-                vm.trap(instruction);
+                return self.trap(instruction);
+            }
+            Err(_) => {return Err(VmError::IllegalOpcode(instruction));}
+        }
+        Ok(StepOutcome::Continue)
+    }
+
+}
+
+/// What happened after a single `Vm::step()` call.
+#[derive(Debug,PartialEq,Eq)]
+pub enum StepOutcome {
+    Continue,
+    Halt,
+}
+
+pub fn main() {
+    let args: Vec<String> = env::args().collect();
+
+    if args.len() < 2 {
+        exit(-2)
+    }
+
+    if args[1] == "asm" {
+        let input = args.get(2).unwrap_or_else(|| {
+            println!("usage: asm <file.asm> [output.obj]");
+            exit(-2);
+        });
+        let source = match std::fs::read_to_string(input) {
+            Ok(source) => source,
+            Err(e) => {
+                println!("Failed to read {}: {}",input,e);
+                exit(1);
+            }
+        };
+        match assembler::assemble(&source) {
+            Ok(bytes) => {
+                let output = args.get(3).cloned().unwrap_or_else(|| format!("{}.obj",input));
+                if let Err(e) = std::fs::write(&output,bytes) {
+                    println!("Failed to write {}: {}",output,e);
+                    exit(1);
+                }
+                println!("Wrote {}",output);
+            }
+            Err(e) => {
+                println!("Assembly failed: {}",e);
+                exit(1);
+            }
+        }
+        return;
+    }
+
+    let debug = args.iter().any(|a| a == "--debug");
+    let disasm_mode = args.iter().any(|a| a == "--disasm");
+    let files:Vec<&String> = args[1..].iter()
+        .filter(|a| a.as_str() != "--debug" && a.as_str() != "--disasm")
+        .collect();
+
+    let mut vm = Vm::new();
+    vm.reg[Registers::PC as usize] = 0x3000; //PC register to start
+
+    if disasm_mode {
+        for file in files {
+            let (origin,result) = match vm.read_image(file.as_str()) {
+                Ok(loaded) => loaded,
+                Err(e) => {
+                    println!("Failed to read from {}: {}",file,e);
+                    continue;
+                }
+            };
+            let count = (result as f64/2.0+0.5) as usize;
+            for i in 0..count {
+                let addr = origin + i as u16;
+                let instruction = vm.mem[addr as usize];
+                println!("x{:04X}: x{:04X}  {}", addr, instruction, disasm(instruction, addr.wrapping_add(1)));
+            }
+        }
+        return;
+    }
+
+    for file in files {
+        match vm.read_image(file.as_str()) {
+            Ok((_,0)) => println!("Failed to read from {}",file),
+            Ok((_,result)) => println!("Read count: {}",result),
+            Err(e) => {
+                println!("Failed to read from {}: {}",file,e);
+                exit(1);
+            }
+        }
+    }
+
+    if debug {
+        let mut debugger = Debugger::new();
+        debugger.run(&mut vm);
+        return;
+    }
+
+    loop {
+        match vm.step() {
+            Ok(StepOutcome::Continue) => {}
+            Ok(StepOutcome::Halt) => {
+                exit(0);
+            }
+            Err(e) => {
+                println!("Ended due to {}",e);
+                exit(1);
             }
-            _ => {break 'l;}
         }
     }
-    println!("Ended due to invalid instruction");
-    exit(0);
 }
\ No newline at end of file